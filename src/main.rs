@@ -1,26 +1,403 @@
-use serde::Deserialize;
+use serde::{de, Deserialize, Deserializer};
 use std::{
-    fs, sync::{Arc, atomic::{AtomicBool, Ordering}}, thread, time::Duration
+    fs,
+    sync::{
+        atomic::{AtomicBool, AtomicI32, Ordering},
+        mpsc::{self, RecvTimeoutError, Sender},
+        Arc, Mutex, OnceLock,
+    },
+    thread,
+    time::{Duration, Instant},
 };
 use windows::{
     core::*,
-    Win32::UI::{
-        Input::KeyboardAndMouse::*,
-        WindowsAndMessaging::*,
+    Win32::{
+        Foundation::*,
+        System::{LibraryLoader::GetModuleHandleW, Threading::GetCurrentThreadId},
+        UI::{
+            Input::KeyboardAndMouse::*,
+            WindowsAndMessaging::*,
+        },
     },
 };
 
-/// 配置结构体，包含点击点信息和延迟设置
+/// 鼠标按键种类
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum MouseButton {
+    /// 左键
+    #[default]
+    Left,
+    /// 右键
+    Right,
+    /// 中键
+    Middle,
+}
+
+impl MouseButton {
+    /// 返回该按键对应的按下/松开事件标志
+    fn down_up(self) -> (MOUSE_EVENT_FLAGS, MOUSE_EVENT_FLAGS) {
+        match self {
+            MouseButton::Left => (MOUSEEVENTF_LEFTDOWN, MOUSEEVENTF_LEFTUP),
+            MouseButton::Right => (MOUSEEVENTF_RIGHTDOWN, MOUSEEVENTF_RIGHTUP),
+            MouseButton::Middle => (MOUSEEVENTF_MIDDLEDOWN, MOUSEEVENTF_MIDDLEUP),
+        }
+    }
+}
+
+/// 点击类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum ClickType {
+    /// 单击
+    #[default]
+    Single,
+    /// 双击（在系统双击时间内连续两次按下/松开）
+    Double,
+}
+
+/// 配置文件中的点击点，兼容旧的四元数组格式与带按键/类型的扩展格式
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(untagged)]
+enum PointConfig {
+    /// 旧格式：[x坐标, y坐标, 点击前延迟(ms), 点击后延迟(ms)]
+    Legacy([i32; 4]),
+    /// 扩展格式：可额外指定按键与点击类型
+    Extended {
+        x: i32,
+        y: i32,
+        pre_delay: i32,
+        post_delay: i32,
+        #[serde(default)]
+        button: MouseButton,
+        #[serde(default)]
+        click_type: ClickType,
+    },
+}
+
+/// 归一化后的点击点
+#[derive(Debug, Clone, Copy)]
+struct Point {
+    /// x坐标
+    x: i32,
+    /// y坐标
+    y: i32,
+    /// 点击前延迟(ms)
+    pre_delay: i32,
+    /// 点击后延迟(ms)
+    post_delay: i32,
+    /// 使用的鼠标按键
+    button: MouseButton,
+    /// 点击类型
+    click_type: ClickType,
+}
+
+impl From<PointConfig> for Point {
+    fn from(config: PointConfig) -> Self {
+        match config {
+            // 旧格式默认左键单击，保持与历史配置一致的行为
+            PointConfig::Legacy([x, y, pre_delay, post_delay]) => Point {
+                x,
+                y,
+                pre_delay,
+                post_delay,
+                button: MouseButton::default(),
+                click_type: ClickType::default(),
+            },
+            PointConfig::Extended {
+                x,
+                y,
+                pre_delay,
+                post_delay,
+                button,
+                click_type,
+            } => Point {
+                x,
+                y,
+                pre_delay,
+                post_delay,
+                button,
+                click_type,
+            },
+        }
+    }
+}
+
+/// 虚拟键码，配置中可写成名称("XBUTTON1"/"F6")或数值(0x75)，加载时解析为 VK 码
+#[derive(Debug, Clone, Copy)]
+struct VirtualKey(i32);
+
+impl VirtualKey {
+    /// 取出底层的虚拟键码
+    fn code(self) -> i32 {
+        self.0
+    }
+}
+
+/// 将虚拟键名称解析为 VK 码，无法识别时返回 `None`
+fn resolve_vk_name(name: &str) -> Option<i32> {
+    let upper = name.trim().to_ascii_uppercase();
+
+    // 十六进制/十进制数值字符串
+    if let Some(hex) = upper.strip_prefix("0X") {
+        if let Ok(code) = i32::from_str_radix(hex, 16) {
+            return Some(code);
+        }
+    }
+    if let Ok(code) = upper.parse::<i32>() {
+        return Some(code);
+    }
+
+    // 功能键 F1..F24
+    if let Some(rest) = upper.strip_prefix('F') {
+        if let Ok(n) = rest.parse::<u16>() {
+            if (1..=24).contains(&n) {
+                return Some((VK_F1.0 + n - 1) as i32);
+            }
+        }
+    }
+
+    // 单个字母/数字与其 ASCII 码(即 VK_A/VK_0)一致
+    if upper.len() == 1 {
+        let c = upper.chars().next().unwrap();
+        if c.is_ascii_alphanumeric() {
+            return Some(c as i32);
+        }
+    }
+
+    let vk = match upper.as_str() {
+        "XBUTTON1" => VK_XBUTTON1,
+        "XBUTTON2" => VK_XBUTTON2,
+        "LBUTTON" => VK_LBUTTON,
+        "RBUTTON" => VK_RBUTTON,
+        "MBUTTON" => VK_MBUTTON,
+        "ESC" | "ESCAPE" => VK_ESCAPE,
+        "SPACE" => VK_SPACE,
+        "TAB" => VK_TAB,
+        "ENTER" | "RETURN" => VK_RETURN,
+        "SHIFT" => VK_SHIFT,
+        "CTRL" | "CONTROL" => VK_CONTROL,
+        "ALT" | "MENU" => VK_MENU,
+        _ => return None,
+    };
+    Some(vk.0 as i32)
+}
+
+impl<'de> Deserialize<'de> for VirtualKey {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct VkVisitor;
+
+        impl de::Visitor<'_> for VkVisitor {
+            type Value = VirtualKey;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("虚拟键名称(如 \"XBUTTON1\"/\"F6\")或键码数值")
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> std::result::Result<VirtualKey, E> {
+                Ok(VirtualKey(v as i32))
+            }
+
+            fn visit_i64<E: de::Error>(self, v: i64) -> std::result::Result<VirtualKey, E> {
+                Ok(VirtualKey(v as i32))
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> std::result::Result<VirtualKey, E> {
+                resolve_vk_name(v)
+                    .map(VirtualKey)
+                    .ok_or_else(|| de::Error::custom(format!("无法识别的按键名称: {v}")))
+            }
+        }
+
+        deserializer.deserialize_any(VkVisitor)
+    }
+}
+
+/// 一个独立的点击配置：拥有各自的触发键、点击点与延迟
 #[derive(Debug, Deserialize, Clone)]
-struct Config {
-    /// 点击点数组，每个点包含 [x坐标, y坐标, 点击前延迟(ms), 点击后延迟(ms)]
-    points: Vec<[i32; 4]>,
+struct Profile {
+    /// 配置名称，便于区分不同的触发行为
+    #[serde(default = "default_profile_name")]
+    name: String,
+    /// 该配置的触发键，接受名称或键码，默认侧键1(XBUTTON1)
+    #[serde(default = "default_trigger_key")]
+    trigger_key: VirtualKey,
+    /// 点击点数组，兼容 `[x, y, 点击前延迟, 点击后延迟]` 或带 `button`/`click_type` 的扩展对象
+    points: Vec<PointConfig>,
     /// 每轮点击前的延迟时间(毫秒)
     pre_round_delay: u64,
     /// 每轮点击后的延迟时间(毫秒)
     post_round_delay: u64,
 }
 
+/// 配置文件的原始形态，兼容“多配置”与旧版“单一扁平”两种写法
+#[derive(Debug, Deserialize, Clone)]
+struct RawConfig {
+    /// 多配置形态：若存在则直接采用
+    profiles: Option<Vec<Profile>>,
+    /// 旧版扁平形态：点击点数组
+    points: Option<Vec<PointConfig>>,
+    /// 旧版扁平形态：每轮点击前延迟(毫秒)
+    #[serde(default)]
+    pre_round_delay: u64,
+    /// 旧版扁平形态：每轮点击后延迟(毫秒)
+    #[serde(default)]
+    post_round_delay: u64,
+    /// 旧版扁平形态：触发键
+    #[serde(default = "default_trigger_key")]
+    trigger_key: VirtualKey,
+    /// 去抖阈值：候选电平需要连续出现的采样次数，达到后才确认跳变
+    #[serde(default = "default_debounce_threshold")]
+    debounce_threshold: u8,
+    /// 去抖采样间隔(毫秒)，即相邻两次按键采样之间的间隔
+    #[serde(default = "default_debounce_interval_ms")]
+    debounce_interval_ms: u64,
+    /// 长按判定阈值(毫秒)：按下持续超过该时长视为长按
+    #[serde(default = "default_long_press_ms")]
+    long_press_ms: u64,
+    /// 双击窗口(毫秒)：一次短按后在该时间内再次按下视为双击
+    #[serde(default = "default_double_click_ms")]
+    double_click_ms: u64,
+    /// 单击手势对应的动作
+    #[serde(default = "default_single_click_action")]
+    single_click_action: TriggerAction,
+    /// 双击手势对应的动作
+    #[serde(default = "default_double_click_action")]
+    double_click_action: TriggerAction,
+    /// 长按手势对应的动作
+    #[serde(default = "default_long_press_action")]
+    long_press_action: TriggerAction,
+    /// 是否使用低层输入钩子（事件驱动）；为 false 时回退到原始的忙轮询路径
+    #[serde(default = "default_use_low_level_hooks")]
+    use_low_level_hooks: bool,
+    /// 退出键，接受名称或键码，默认 ESC
+    #[serde(default = "default_quit_key")]
+    quit_key: VirtualKey,
+}
+
+/// 配置结构体，包含若干独立配置及全局手势/退出设置
+#[derive(Debug, Clone)]
+struct Config {
+    /// 并发监控的独立配置列表，每个拥有各自的触发键与点击序列
+    profiles: Vec<Profile>,
+    /// 去抖阈值：候选电平需要连续出现的采样次数，达到后才确认跳变
+    debounce_threshold: u8,
+    /// 去抖采样间隔(毫秒)，即相邻两次按键采样之间的间隔
+    debounce_interval_ms: u64,
+    /// 长按判定阈值(毫秒)：按下持续超过该时长视为长按
+    long_press_ms: u64,
+    /// 双击窗口(毫秒)：一次短按后在该时间内再次按下视为双击
+    double_click_ms: u64,
+    /// 单击手势对应的动作
+    single_click_action: TriggerAction,
+    /// 双击手势对应的动作
+    double_click_action: TriggerAction,
+    /// 长按手势对应的动作
+    long_press_action: TriggerAction,
+    /// 是否使用低层输入钩子（事件驱动）；为 false 时回退到原始的忙轮询路径
+    use_low_level_hooks: bool,
+    /// 退出键，接受名称或键码，默认 ESC
+    quit_key: VirtualKey,
+}
+
+impl From<RawConfig> for Config {
+    fn from(raw: RawConfig) -> Self {
+        // 优先采用多配置形态；否则把旧版扁平字段包装成单个默认配置，保持向后兼容
+        let profiles = raw.profiles.unwrap_or_else(|| {
+            vec![Profile {
+                name: default_profile_name(),
+                trigger_key: raw.trigger_key,
+                points: raw.points.unwrap_or_default(),
+                pre_round_delay: raw.pre_round_delay,
+                post_round_delay: raw.post_round_delay,
+            }]
+        });
+
+        Config {
+            profiles,
+            debounce_threshold: raw.debounce_threshold,
+            debounce_interval_ms: raw.debounce_interval_ms,
+            long_press_ms: raw.long_press_ms,
+            double_click_ms: raw.double_click_ms,
+            single_click_action: raw.single_click_action,
+            double_click_action: raw.double_click_action,
+            long_press_action: raw.long_press_action,
+            use_low_level_hooks: raw.use_low_level_hooks,
+            quit_key: raw.quit_key,
+        }
+    }
+}
+
+/// 触发手势所映射的动作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum TriggerAction {
+    /// 切换运行：按下即在“持续点击”与“停止”之间自锁切换，无需一直按住
+    ToggleRun,
+    /// 单次执行：触发一次完整的点击序列后自动停止
+    OneShot,
+    /// 按住运行：按住期间持续点击，松开即停止（原始的 hold-to-run 行为）
+    HoldToRun,
+}
+
+/// 默认去抖阈值：3 次连续采样(3/3)
+fn default_debounce_threshold() -> u8 {
+    3
+}
+
+/// 默认去抖采样间隔(毫秒)
+fn default_debounce_interval_ms() -> u64 {
+    3
+}
+
+/// 默认长按判定阈值(毫秒)
+fn default_long_press_ms() -> u64 {
+    400
+}
+
+/// 默认双击窗口(毫秒)
+fn default_double_click_ms() -> u64 {
+    300
+}
+
+/// 默认单击动作：切换运行
+fn default_single_click_action() -> TriggerAction {
+    TriggerAction::ToggleRun
+}
+
+/// 默认双击动作：单次执行
+fn default_double_click_action() -> TriggerAction {
+    TriggerAction::OneShot
+}
+
+/// 默认长按动作：按住运行
+fn default_long_press_action() -> TriggerAction {
+    TriggerAction::HoldToRun
+}
+
+/// 默认启用低层输入钩子
+fn default_use_low_level_hooks() -> bool {
+    true
+}
+
+/// 默认启动触发键：鼠标侧键1(XBUTTON1)
+fn default_trigger_key() -> VirtualKey {
+    VirtualKey(VK_XBUTTON1.0 as i32)
+}
+
+/// 默认退出键：ESC
+fn default_quit_key() -> VirtualKey {
+    VirtualKey(VK_ESCAPE.0 as i32)
+}
+
+/// 默认配置名称
+fn default_profile_name() -> String {
+    "default".to_string()
+}
+
 impl Config {
     /// 从JSON文件加载配置
     fn from_file(filename: &str) -> Result<Self> {
@@ -28,9 +405,167 @@ impl Config {
         let content = fs::read_to_string(filename)
             .map_err(|_| Error::new(HRESULT(0), "无法读取配置文件"))?;
 
-        // 直接解析为配置结构体
-        serde_json::from_str(&content)
-            .map_err(|_| Error::new(HRESULT(0), "配置文件格式错误"))
+        // 先解析为原始形态，再归一化为内部配置（兼容旧版扁平写法）
+        let raw: RawConfig = serde_json::from_str(&content)
+            .map_err(|_| Error::new(HRESULT(0), "配置文件格式错误"))?;
+        Ok(raw.into())
+    }
+}
+
+/// 去抖后检测到的稳定电平跳变（边沿事件）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum KeyEdge {
+    /// 按下边沿（稳定电平由松开变为按下）
+    Pressed,
+    /// 松开边沿（稳定电平由按下变为松开）
+    Released,
+}
+
+/// 单个被监控虚拟键的去抖状态
+///
+/// 原始采样会因电气/驱动抖动而频繁跳变，这里只有当新电平在连续
+/// `threshold` 次采样中保持一致时才确认为稳定电平并产生边沿事件，
+/// 从而消除侧键触点抖动导致的重复启停。
+struct KeyDebouncer {
+    /// 当前已确认的稳定电平
+    last_stable: bool,
+    /// 与稳定电平不一致的候选电平连续出现的次数
+    candidate_count: u8,
+}
+
+impl KeyDebouncer {
+    /// 以给定初始电平创建去抖器
+    fn new(initial: bool) -> Self {
+        Self {
+            last_stable: initial,
+            candidate_count: 0,
+        }
+    }
+
+    /// 用一次原始采样更新去抖状态
+    ///
+    /// 若采样与稳定电平一致则清零候选计数；否则累加候选计数，
+    /// 当其达到 `threshold` 时提交新的稳定电平并返回对应边沿事件。
+    fn update(&mut self, raw: bool, threshold: u8) -> Option<KeyEdge> {
+        if raw == self.last_stable {
+            self.candidate_count = 0;
+            return None;
+        }
+
+        self.candidate_count = self.candidate_count.saturating_add(1);
+        if self.candidate_count >= threshold {
+            self.last_stable = raw;
+            self.candidate_count = 0;
+            return Some(if raw {
+                KeyEdge::Pressed
+            } else {
+                KeyEdge::Released
+            });
+        }
+
+        None
+    }
+}
+
+/// 由按压时长状态机识别出的手势
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Gesture {
+    /// 单击（短按后在双击窗口内未再次按下）
+    SingleClick,
+    /// 双击（短按后在双击窗口内再次按下并松开）
+    DoubleClick,
+    /// 长按开始（按住超过长按阈值）
+    LongPressStart,
+    /// 长按结束（长按状态下松开）
+    LongPressEnd,
+}
+
+/// 触发键按压时长状态机
+///
+/// 在触发键边沿（轮询路径经去抖，钩子路径直接来自钩子事件）之上叠加时间维度，把“按住不放”的单一模型
+/// 扩展为单击/双击/长按三种可配置的手势。
+enum TriggerState {
+    /// 空闲：无按键活动
+    Idle,
+    /// 按下去抖中：已按下，尚未判定短按或长按
+    DownDebounce { pressed_at: Instant },
+    /// 短按已释放：等待可能的第二次按下以构成双击
+    PressedShort { window_start: Instant },
+    /// 长按保持中：已越过长按阈值且仍按住
+    DownLong,
+    /// 双击等待中：双击窗口内的第二次按下仍处于按住状态
+    DoubleClickWait,
+}
+
+impl TriggerState {
+    /// 创建处于空闲态的状态机
+    fn new() -> Self {
+        TriggerState::Idle
+    }
+
+    /// 用一次（可能为空的）去抖边沿推进状态机
+    ///
+    /// `now` 为当前时刻，`long_press_ms`/`double_click_ms` 为判定阈值；
+    /// 返回本次推进所识别出的手势（若有）。
+    fn advance(
+        &mut self,
+        edge: Option<KeyEdge>,
+        now: Instant,
+        long_press_ms: u64,
+        double_click_ms: u64,
+    ) -> Option<Gesture> {
+        match *self {
+            TriggerState::Idle => {
+                if let Some(KeyEdge::Pressed) = edge {
+                    *self = TriggerState::DownDebounce { pressed_at: now };
+                }
+                None
+            }
+            TriggerState::DownDebounce { pressed_at } => {
+                if let Some(KeyEdge::Released) = edge {
+                    // 长按阈值之前松开 → 短按，进入双击等待窗口
+                    *self = TriggerState::PressedShort { window_start: now };
+                    None
+                } else if now.duration_since(pressed_at) >= Duration::from_millis(long_press_ms) {
+                    // 仍按住且越过阈值 → 长按开始
+                    *self = TriggerState::DownLong;
+                    Some(Gesture::LongPressStart)
+                } else {
+                    None
+                }
+            }
+            TriggerState::PressedShort { window_start } => {
+                if let Some(KeyEdge::Pressed) = edge {
+                    // 窗口内再次按下 → 可能的双击，等待其松开
+                    *self = TriggerState::DoubleClickWait;
+                    None
+                } else if now.duration_since(window_start)
+                    >= Duration::from_millis(double_click_ms)
+                {
+                    // 窗口超时且无第二次按下 → 确认单击
+                    *self = TriggerState::Idle;
+                    Some(Gesture::SingleClick)
+                } else {
+                    None
+                }
+            }
+            TriggerState::DownLong => {
+                if let Some(KeyEdge::Released) = edge {
+                    *self = TriggerState::Idle;
+                    Some(Gesture::LongPressEnd)
+                } else {
+                    None
+                }
+            }
+            TriggerState::DoubleClickWait => {
+                if let Some(KeyEdge::Released) = edge {
+                    *self = TriggerState::Idle;
+                    Some(Gesture::DoubleClick)
+                } else {
+                    None
+                }
+            }
+        }
     }
 }
 
@@ -57,43 +592,39 @@ fn set_dpi_awareness() -> Result<()> {
     }
 }
 
-/// 模拟鼠标左键点击
-fn simulate_click(x: i32, y: i32) -> Result<()> {
+/// 构造一个携带指定事件标志的鼠标输入事件
+fn mouse_input(flag: MOUSE_EVENT_FLAGS) -> INPUT {
+    INPUT {
+        r#type: INPUT_MOUSE,
+        Anonymous: INPUT_0 {
+            mi: MOUSEINPUT {
+                dx: 0,
+                dy: 0,
+                mouseData: 0,
+                dwFlags: flag,
+                time: 0,
+                dwExtraInfo: 0,
+            },
+        },
+    }
+}
+
+/// 在指定坐标模拟一次鼠标点击，可选择按键(左/右/中)与类型(单击/双击)
+fn simulate_click(x: i32, y: i32, button: MouseButton, click_type: ClickType) -> Result<()> {
     unsafe {
         // 先使用SetCursorPos设置鼠标位置
         SetCursorPos(x, y).map_err(|_| Error::new(HRESULT(0), "设置鼠标位置失败"))?;
 
-        let mut inputs = [
-            INPUT {
-                r#type: INPUT_MOUSE,
-                Anonymous: INPUT_0 {
-                    mi: MOUSEINPUT {
-                        dx: 0,
-                        dy: 0,
-                        mouseData: 0,
-                        dwFlags: MOUSEEVENTF_LEFTDOWN,
-                        time: 0,
-                        dwExtraInfo: 0,
-                    },
-                },
-            },
-            INPUT {
-                r#type: INPUT_MOUSE,
-                Anonymous: INPUT_0 {
-                    mi: MOUSEINPUT {
-                        dx: 0,
-                        dy: 0,
-                        mouseData: 0,
-                        dwFlags: MOUSEEVENTF_LEFTUP,
-                        time: 0,
-                        dwExtraInfo: 0,
-                    },
-                },
-            },
-        ];
+        // 组装按下/松开事件；双击在系统双击时间内发送两对按下/松开
+        let (down, up) = button.down_up();
+        let mut inputs = vec![mouse_input(down), mouse_input(up)];
+        if click_type == ClickType::Double {
+            inputs.push(mouse_input(down));
+            inputs.push(mouse_input(up));
+        }
 
         // 发送鼠标按下和松开事件
-        let sent = SendInput(&mut inputs, std::mem::size_of::<INPUT>() as i32);
+        let sent = SendInput(&inputs, std::mem::size_of::<INPUT>() as i32);
         if sent != inputs.len() as u32 {
             return Err(Error::new(HRESULT(0), "鼠标点击模拟失败"));
         }
@@ -102,160 +633,487 @@ fn simulate_click(x: i32, y: i32) -> Result<()> {
     }
 }
 
-/// 检查鼠标侧键1是否被按下
-fn is_side_button1_pressed() -> bool {
+/// 检查指定虚拟键是否被按下
+fn is_key_pressed(vk: i32) -> bool {
     unsafe {
-        // 检查侧键1按下状态
-        (GetAsyncKeyState(VK_XBUTTON1.0 as i32) as u16 & 0x8000) != 0
+        // 检查该键的按下状态
+        (GetAsyncKeyState(vk) as u16 & 0x8000) != 0
     }
 }
 
-/// 检查ESC键是否被按下
-fn is_escape_pressed() -> bool {
-    unsafe {
-        // 检查ESC键按下状态
-        (GetAsyncKeyState(VK_ESCAPE.0 as i32) as u16 & 0x8000) != 0
+/// 分段睡眠，每 10ms 检查一次停止信号，便于及时响应停止
+fn interruptible_sleep(mut remaining_delay: u64, should_stop: &Arc<AtomicBool>) {
+    while remaining_delay > 0 && !should_stop.load(Ordering::Relaxed) {
+        let sleep_time = if remaining_delay > 10 { 10 } else { remaining_delay };
+        high_precision_sleep(sleep_time);
+        remaining_delay -= sleep_time;
     }
 }
 
-/// 执行点击序列（在子线程中运行）
-fn execute_click_sequence(config: &Config, should_stop: Arc<AtomicBool>) {
-    // 持续循环执行点击序列，直到收到停止信号
-    loop {
-        // 每次循环开始时检查是否应该停止
+/// 执行一轮完整的点击序列（含轮前/轮后延迟），可被停止信号中断
+fn execute_click_round(profile: &Profile, should_stop: &Arc<AtomicBool>) {
+    // 每轮开始前的延迟
+    if profile.pre_round_delay > 0 {
+        interruptible_sleep(profile.pre_round_delay, should_stop);
+    }
+
+    // 如果已经收到停止信号，直接返回
+    if should_stop.load(Ordering::Relaxed) {
+        return;
+    }
+
+    // 执行一轮点击序列
+    for point in &profile.points {
+        // 检查是否应该停止
         if should_stop.load(Ordering::Relaxed) {
             break;
         }
 
-        // 每轮开始前的延迟
-        if config.pre_round_delay > 0 {
-            let mut remaining_delay = config.pre_round_delay;
-            while remaining_delay > 0 && !should_stop.load(Ordering::Relaxed) {
-                let sleep_time = if remaining_delay > 10 { 10 } else { remaining_delay };
-                high_precision_sleep(sleep_time);
-                remaining_delay -= sleep_time;
-            }
+        let Point {
+            x,
+            y,
+            pre_delay,
+            post_delay,
+            button,
+            click_type,
+        } = Point::from(*point);
+
+        // 点击前延迟 - 分段检查停止信号
+        if pre_delay > 0 {
+            interruptible_sleep(pre_delay as u64, should_stop);
         }
 
-        // 如果已经收到停止信号，直接返回
+        // 再次检查停止信号
         if should_stop.load(Ordering::Relaxed) {
             break;
         }
 
-        // 执行一轮点击序列
-        for point in &config.points {
-            // 检查是否应该停止
-            if should_stop.load(Ordering::Relaxed) {
-                break;
-            }
+        // 执行点击
+        let _ = simulate_click(x, y, button, click_type);
 
-            let [x, y, pre_delay, post_delay] = *point;
+        // 点击后延迟 - 分段检查停止信号
+        if post_delay > 0 {
+            interruptible_sleep(post_delay as u64, should_stop);
+        }
+    }
 
-            // 点击前延迟 - 分段检查停止信号
-            if pre_delay > 0 {
-                let mut remaining_delay = pre_delay as u64;
-                while remaining_delay > 0 && !should_stop.load(Ordering::Relaxed) {
-                    let sleep_time = if remaining_delay > 10 { 10 } else { remaining_delay };
-                    high_precision_sleep(sleep_time);
-                    remaining_delay -= sleep_time;
-                }
-            }
+    // 每轮结束后的延迟
+    if profile.post_round_delay > 0 {
+        interruptible_sleep(profile.post_round_delay, should_stop);
+    }
+}
 
-            // 再次检查停止信号
-            if should_stop.load(Ordering::Relaxed) {
-                break;
-            }
+/// 执行点击序列（在子线程中运行），持续循环直到收到停止信号
+fn execute_click_sequence(profile: &Profile, should_stop: Arc<AtomicBool>) {
+    // 持续循环执行点击序列，直到收到停止信号
+    while !should_stop.load(Ordering::Relaxed) {
+        execute_click_round(profile, &should_stop);
+    }
+}
 
-            // 执行点击
-            let _ = simulate_click(x, y);
+/// 启动一个持续点击的工作线程（若已在运行则忽略）
+fn start_clicking(
+    handle: &mut Option<thread::JoinHandle<()>>,
+    should_stop: &Arc<AtomicBool>,
+    profile: &Profile,
+) {
+    if handle.is_some() {
+        return;
+    }
+    should_stop.store(false, Ordering::Relaxed);
+    let profile_clone = profile.clone();
+    let should_stop_clone = Arc::clone(should_stop);
+    *handle = Some(thread::spawn(move || {
+        execute_click_sequence(&profile_clone, should_stop_clone);
+    }));
+}
 
-            // 点击后延迟 - 分段检查停止信号
-            if post_delay > 0 {
-                let mut remaining_delay = post_delay as u64;
-                while remaining_delay > 0 && !should_stop.load(Ordering::Relaxed) {
-                    let sleep_time = if remaining_delay > 10 { 10 } else { remaining_delay };
-                    high_precision_sleep(sleep_time);
-                    remaining_delay -= sleep_time;
-                }
+/// 停止正在运行的点击线程并等待其结束
+fn stop_clicking(handle: &mut Option<thread::JoinHandle<()>>, should_stop: &Arc<AtomicBool>) {
+    should_stop.store(true, Ordering::Relaxed);
+    if let Some(h) = handle.take() {
+        let _ = h.join();
+    }
+}
+
+/// 触发一次性的单轮点击（若已在运行则忽略）
+fn one_shot_clicking(
+    handle: &mut Option<thread::JoinHandle<()>>,
+    should_stop: &Arc<AtomicBool>,
+    profile: &Profile,
+) {
+    if handle.is_some() {
+        return;
+    }
+    should_stop.store(false, Ordering::Relaxed);
+    let profile_clone = profile.clone();
+    let should_stop_clone = Arc::clone(should_stop);
+    *handle = Some(thread::spawn(move || {
+        execute_click_round(&profile_clone, &should_stop_clone);
+    }));
+}
+
+/// 将配置的动作应用到点击线程（长按的按住语义由调用方单独处理）
+fn apply_action(
+    action: TriggerAction,
+    handle: &mut Option<thread::JoinHandle<()>>,
+    should_stop: &Arc<AtomicBool>,
+    profile: &Profile,
+) {
+    match action {
+        // 切换运行：正在运行则停止，否则启动自锁点击
+        TriggerAction::ToggleRun => {
+            if handle.is_some() {
+                stop_clicking(handle, should_stop);
+            } else {
+                start_clicking(handle, should_stop, profile);
             }
         }
+        // 单次执行：触发一轮点击
+        TriggerAction::OneShot => one_shot_clicking(handle, should_stop, profile),
+        // 按住运行：作为瞬时手势时等价于启动
+        TriggerAction::HoldToRun => start_clicking(handle, should_stop, profile),
+    }
+}
+
+/// 回收已结束的一次性点击线程，使其后的手势能重新触发
+fn reap_finished(handle: &mut Option<thread::JoinHandle<()>>) {
+    if handle.as_ref().is_some_and(|h| h.is_finished()) {
+        if let Some(h) = handle.take() {
+            let _ = h.join();
+        }
+    }
+}
 
-        // 每轮结束后的延迟
-        if config.post_round_delay > 0 {
-            let mut remaining_delay = config.post_round_delay;
-            while remaining_delay > 0 && !should_stop.load(Ordering::Relaxed) {
-                let sleep_time = if remaining_delay > 10 { 10 } else { remaining_delay };
-                high_precision_sleep(sleep_time);
-                remaining_delay -= sleep_time;
+/// 将状态机识别出的手势映射为配置的动作并施加到对应配置的点击线程
+fn dispatch_gesture(
+    gesture: Gesture,
+    config: &Config,
+    profile: &Profile,
+    handle: &mut Option<thread::JoinHandle<()>>,
+    should_stop: &Arc<AtomicBool>,
+) {
+    match gesture {
+        Gesture::SingleClick => {
+            apply_action(config.single_click_action, handle, should_stop, profile)
+        }
+        Gesture::DoubleClick => {
+            apply_action(config.double_click_action, handle, should_stop, profile)
+        }
+        // 长按开始：按住运行语义下启动，其余动作按一次性手势处理
+        Gesture::LongPressStart => {
+            if config.long_press_action == TriggerAction::HoldToRun {
+                start_clicking(handle, should_stop, profile);
+            } else {
+                apply_action(config.long_press_action, handle, should_stop, profile);
+            }
+        }
+        // 长按结束：仅在按住运行语义下停止
+        Gesture::LongPressEnd => {
+            if config.long_press_action == TriggerAction::HoldToRun {
+                stop_clicking(handle, should_stop);
             }
         }
     }
 }
 
-fn main() -> Result<()> {
-    // 设置DPI感知，确保坐标精度
-    let _ = set_dpi_awareness();
+/// 单个配置的运行时状态：各自的停止标志、点击线程、去抖器与手势状态机
+struct ProfileRuntime {
+    /// 该运行时对应的配置
+    profile: Profile,
+    /// 该配置点击线程的停止标志
+    should_stop: Arc<AtomicBool>,
+    /// 正在运行的点击线程句柄
+    handle: Option<thread::JoinHandle<()>>,
+    /// 触发键去抖器（轮询路径使用）
+    debouncer: KeyDebouncer,
+    /// 触发键按压时长状态机
+    trigger_state: TriggerState,
+}
 
-    // 从配置文件加载设置
-    let config = Config::from_file("config.json")
-        .map_err(|_| Error::new(HRESULT(0), "配置文件不存在或格式错误"))?;
+impl ProfileRuntime {
+    /// 为一个配置创建运行时状态
+    fn new(profile: Profile) -> Self {
+        Self {
+            profile,
+            should_stop: Arc::new(AtomicBool::new(false)),
+            handle: None,
+            debouncer: KeyDebouncer::new(false),
+            trigger_state: TriggerState::new(),
+        }
+    }
+
+    /// 回收已结束的一次性点击线程
+    fn reap(&mut self) {
+        reap_finished(&mut self.handle);
+    }
 
-    // 创建共享的停止标志
-    let should_stop = Arc::new(AtomicBool::new(false));
+    /// 用一个（可能为空的）去抖边沿推进该配置的状态机并施加动作
+    fn drive(&mut self, edge: Option<KeyEdge>, config: &Config) {
+        if let Some(gesture) = self.trigger_state.advance(
+            edge,
+            Instant::now(),
+            config.long_press_ms,
+            config.double_click_ms,
+        ) {
+            dispatch_gesture(
+                gesture,
+                config,
+                &self.profile,
+                &mut self.handle,
+                &self.should_stop,
+            );
+        }
+    }
 
-    // 跟踪是否有正在运行的点击线程
-    let mut click_thread_handle: Option<thread::JoinHandle<()>> = None;
+    /// 停止该配置的点击线程并等待其结束
+    fn stop(&mut self) {
+        stop_clicking(&mut self.handle, &self.should_stop);
+    }
+}
+
+/// 低层钩子投递给控制线程的原始输入事件
+enum HookEvent {
+    /// 某个触发键电平变化，携带其虚拟键码与电平（true 为按下）
+    TriggerLevel { vk: i32, down: bool },
+    /// 退出键按下
+    Quit,
+}
+
+/// 钩子回调向控制线程投递事件所用的全局发送端
+///
+/// 低层钩子回调是 C 约定的自由函数，无法捕获环境，只能通过全局量
+/// 把原始输入转交给控制线程。
+static HOOK_SENDER: OnceLock<Mutex<Sender<HookEvent>>> = OnceLock::new();
+
+/// 钩子回调读取的触发键集合与退出键 VK 码（回调无法捕获环境，只能走全局量）
+static TRIGGER_VKS: OnceLock<Vec<i32>> = OnceLock::new();
+static QUIT_VK: AtomicI32 = AtomicI32::new(0);
+
+/// 向控制线程投递一个钩子事件（发送端未就绪时静默丢弃）
+fn post_hook_event(event: HookEvent) {
+    if let Some(lock) = HOOK_SENDER.get() {
+        if let Ok(tx) = lock.lock() {
+            let _ = tx.send(event);
+        }
+    }
+}
+
+/// 将钩子捕获到的按键与配置的触发键/退出键比对并投递相应事件
+fn handle_hook_key(vk: i32, is_down: bool) {
+    if is_down && vk == QUIT_VK.load(Ordering::Relaxed) {
+        post_hook_event(HookEvent::Quit);
+    } else if TRIGGER_VKS.get().is_some_and(|vks| vks.contains(&vk)) {
+        post_hook_event(HookEvent::TriggerLevel { vk, down: is_down });
+    }
+}
+
+/// 将 X 键编号(mouseData 高位字)映射为对应的虚拟键码
+fn xbutton_vk(xbutton: u16) -> i32 {
+    match xbutton {
+        0x0001 => VK_XBUTTON1.0 as i32,
+        0x0002 => VK_XBUTTON2.0 as i32,
+        _ => 0,
+    }
+}
+
+/// WH_MOUSE_LL 回调：把鼠标按键事件映射为虚拟键码后交由统一处理
+unsafe extern "system" fn mouse_hook_proc(code: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+    if code == HC_ACTION as i32 {
+        let info = &*(lparam.0 as *const MSLLHOOKSTRUCT);
+        // X 键编号位于 mouseData 的高位字
+        let xbutton = ((info.mouseData >> 16) & 0xFFFF) as u16;
+        let mapped = match wparam.0 as u32 {
+            WM_LBUTTONDOWN => Some((VK_LBUTTON.0 as i32, true)),
+            WM_LBUTTONUP => Some((VK_LBUTTON.0 as i32, false)),
+            WM_RBUTTONDOWN => Some((VK_RBUTTON.0 as i32, true)),
+            WM_RBUTTONUP => Some((VK_RBUTTON.0 as i32, false)),
+            WM_MBUTTONDOWN => Some((VK_MBUTTON.0 as i32, true)),
+            WM_MBUTTONUP => Some((VK_MBUTTON.0 as i32, false)),
+            WM_XBUTTONDOWN => Some((xbutton_vk(xbutton), true)),
+            WM_XBUTTONUP => Some((xbutton_vk(xbutton), false)),
+            _ => None,
+        };
+        if let Some((vk, is_down)) = mapped {
+            handle_hook_key(vk, is_down);
+        }
+    }
+    CallNextHookEx(None, code, wparam, lparam)
+}
+
+/// WH_KEYBOARD_LL 回调：把键盘按键事件交由统一处理
+unsafe extern "system" fn keyboard_hook_proc(code: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+    if code == HC_ACTION as i32 {
+        let info = &*(lparam.0 as *const KBDLLHOOKSTRUCT);
+        let vk = info.vkCode as i32;
+        match wparam.0 as u32 {
+            WM_KEYDOWN | WM_SYSKEYDOWN => handle_hook_key(vk, true),
+            WM_KEYUP | WM_SYSKEYUP => handle_hook_key(vk, false),
+            _ => {}
+        }
+    }
+    CallNextHookEx(None, code, wparam, lparam)
+}
+
+/// 控制线程：消费钩子事件，经状态机识别手势后驱动点击线程
+///
+/// 使用带超时的 `recv` 以便在无输入事件时仍能推进状态机基于时间的
+/// 跳变（长按阈值、双击窗口），并在收到退出事件后向消息泵线程投递
+/// `WM_QUIT` 以结束程序。
+fn hook_control_loop(config: &Config, rx: mpsc::Receiver<HookEvent>, pump_thread_id: u32) {
+    // 每个配置各自的运行时状态，并发监控各自的触发键
+    let mut runtimes: Vec<ProfileRuntime> =
+        config.profiles.iter().cloned().map(ProfileRuntime::new).collect();
+    // 超时粒度取去抖采样间隔，至少 1ms，保证时间跳变有足够分辨率
+    let tick = Duration::from_millis(config.debounce_interval_ms.max(1));
 
-    // 主循环：监听鼠标侧键1控制自动点击
     loop {
-        // 检查ESC键退出程序
-        if is_escape_pressed() {
-            // 设置停止标志，让正在运行的点击线程停止
-            should_stop.store(true, Ordering::Relaxed);
-
-            // 等待点击线程结束
-            if let Some(handle) = click_thread_handle.take() {
-                let _ = handle.join();
+        for rt in &mut runtimes {
+            rt.reap();
+        }
+
+        match rx.recv_timeout(tick) {
+            // 仅把边沿交给触发键匹配的配置，其余配置只推进时间
+            Ok(HookEvent::TriggerLevel { vk, down }) => {
+                let edge = if down {
+                    KeyEdge::Pressed
+                } else {
+                    KeyEdge::Released
+                };
+                for rt in &mut runtimes {
+                    let e = if rt.profile.trigger_key.code() == vk {
+                        Some(edge)
+                    } else {
+                        None
+                    };
+                    rt.drive(e, config);
+                }
             }
-            break;
+            Ok(HookEvent::Quit) => {
+                for rt in &mut runtimes {
+                    rt.stop();
+                }
+                break;
+            }
+            // 无事件时推进各配置状态机基于时间的跳变
+            Err(RecvTimeoutError::Timeout) => {
+                for rt in &mut runtimes {
+                    rt.drive(None, config);
+                }
+            }
+            Err(RecvTimeoutError::Disconnected) => break,
         }
+    }
 
-        let side_button1_pressed = is_side_button1_pressed();
+    // 解除消息泵线程的 GetMessage 阻塞，使其卸载钩子并退出
+    unsafe {
+        let _ = PostThreadMessageW(pump_thread_id, WM_QUIT, WPARAM(0), LPARAM(0));
+    }
+}
+
+/// 事件驱动路径：安装低层鼠标/键盘钩子并运行消息泵
+///
+/// 钩子回调把输入事件投递给控制线程，本线程阻塞在 `GetMessage` 上，
+/// 仅在 OS 递送输入事件时被唤醒，避免忙轮询带来的 CPU 开销。
+fn run_with_hooks(config: &Config) -> Result<()> {
+    let (tx, rx) = mpsc::channel();
+    HOOK_SENDER
+        .set(Mutex::new(tx))
+        .map_err(|_| Error::new(HRESULT(0), "钩子发送端已初始化"))?;
 
-        // 如果侧键1被按下且当前没有点击线程在运行
-        if side_button1_pressed && click_thread_handle.is_none() {
-            // 重置停止标志
-            should_stop.store(false, Ordering::Relaxed);
+    // 把各配置的触发键与退出键暴露给钩子回调
+    let trigger_vks: Vec<i32> = config
+        .profiles
+        .iter()
+        .map(|p| p.trigger_key.code())
+        .collect();
+    let _ = TRIGGER_VKS.set(trigger_vks);
+    QUIT_VK.store(config.quit_key.code(), Ordering::Relaxed);
 
-            // 克隆配置和共享状态
-            let config_clone = config.clone();
-            let should_stop_clone = Arc::clone(&should_stop);
+    // 记录消息泵线程(本线程)的 id，供控制线程投递 WM_QUIT
+    let pump_thread_id = unsafe { GetCurrentThreadId() };
 
-            // 启动点击线程
-            click_thread_handle = Some(thread::spawn(move || {
-                execute_click_sequence(&config_clone, should_stop_clone);
-            }));
+    // 控制线程独立于消息泵，驱动状态机与点击线程
+    let config_clone = config.clone();
+    let control = thread::spawn(move || {
+        hook_control_loop(&config_clone, rx, pump_thread_id);
+    });
+
+    unsafe {
+        let hmod = GetModuleHandleW(None)?;
+        let mouse_hook = SetWindowsHookExW(WH_MOUSE_LL, Some(mouse_hook_proc), hmod, 0)?;
+        let keyboard_hook = SetWindowsHookExW(WH_KEYBOARD_LL, Some(keyboard_hook_proc), hmod, 0)?;
+
+        // 消息泵：睡眠直至 OS 递送输入事件，收到 WM_QUIT 时退出
+        let mut msg = MSG::default();
+        while GetMessageW(&mut msg, None, 0, 0).as_bool() {
+            let _ = TranslateMessage(&msg);
+            DispatchMessageW(&msg);
         }
-        // 如果侧键1松开但有点击线程在运行
-        else if !side_button1_pressed && click_thread_handle.is_some() {
-            // 设置停止标志
-            should_stop.store(true, Ordering::Relaxed);
 
-            // 等待点击线程结束
-            if let Some(handle) = click_thread_handle.take() {
-                let _ = handle.join();
-            }
+        let _ = UnhookWindowsHookEx(mouse_hook);
+        let _ = UnhookWindowsHookEx(keyboard_hook);
+    }
+
+    let _ = control.join();
+    Ok(())
+}
+
+/// 轮询路径（回退方案）：忙轮询 `GetAsyncKeyState`，并发监控各配置的触发键
+fn run_with_polling(config: &Config) -> Result<()> {
+    // 每个配置各自的运行时状态
+    let mut runtimes: Vec<ProfileRuntime> =
+        config.profiles.iter().cloned().map(ProfileRuntime::new).collect();
+
+    // 退出键去抖器
+    let mut quit_debouncer = KeyDebouncer::new(false);
+
+    // 主循环：并发监控各配置触发键的手势控制自动点击
+    loop {
+        // 回收各配置已结束的一次性点击线程，使其后的手势能重新触发
+        for rt in &mut runtimes {
+            rt.reap();
         }
-        // 如果没有点击线程在运行，降低CPU占用率
-        else if click_thread_handle.is_none() {
-            high_precision_sleep(10);
+
+        // 去抖后的退出键按下边沿：停止所有配置并退出程序
+        if let Some(KeyEdge::Pressed) = quit_debouncer.update(
+            is_key_pressed(config.quit_key.code()),
+            config.debounce_threshold,
+        ) {
+            for rt in &mut runtimes {
+                rt.stop();
+            }
+            break;
         }
 
-        // 如果有点击线程在运行，短暂延迟后继续检查按键状态
-        if click_thread_handle.is_some() {
-            high_precision_sleep(1);
+        // 逐个配置采样其触发键，去抖后经状态机识别手势并映射为动作
+        for rt in &mut runtimes {
+            let edge = rt.debouncer.update(
+                is_key_pressed(rt.profile.trigger_key.code()),
+                config.debounce_threshold,
+            );
+            rt.drive(edge, config);
         }
+
+        // 按去抖采样间隔等待下一次采样
+        high_precision_sleep(config.debounce_interval_ms);
     }
 
     Ok(())
+}
+
+fn main() -> Result<()> {
+    // 设置DPI感知，确保坐标精度
+    let _ = set_dpi_awareness();
+
+    // 从配置文件加载设置
+    let config = Config::from_file("config.json")
+        .map_err(|_| Error::new(HRESULT(0), "配置文件不存在或格式错误"))?;
+
+    // 默认走事件驱动的低层钩子路径；在全局钩子被禁用的环境下回退到轮询
+    if config.use_low_level_hooks {
+        run_with_hooks(&config)
+    } else {
+        run_with_polling(&config)
+    }
 }
\ No newline at end of file